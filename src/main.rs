@@ -1,10 +1,62 @@
+use rand::Rng;
+
 const HZ: u32 = 700;
 const DHZ: u32 = 60;
+const MEM_SIZE: usize = 0x1000;
+const STACK_LIMIT: usize = 16;
+
+const FONT_ADDR: usize = 0x050;
+const FONT: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+
+// Conventional 4x4 hex keypad layout mapped onto the left-hand side of a QWERTY keyboard:
+//   1 2 3 4        1 2 3 C
+//   Q W E R   -->  4 5 6 D
+//   A S D F        7 8 9 E
+//   Z X C V        A 0 B F
+const KEYMAP: [(winit::event::VirtualKeyCode, u8); 16] = {
+    use winit::event::VirtualKeyCode::*;
+    [
+        (Key1, 0x1),
+        (Key2, 0x2),
+        (Key3, 0x3),
+        (Key4, 0xc),
+        (Q, 0x4),
+        (W, 0x5),
+        (E, 0x6),
+        (R, 0xd),
+        (A, 0x7),
+        (S, 0x8),
+        (D, 0x9),
+        (F, 0xe),
+        (Z, 0xa),
+        (X, 0x0),
+        (C, 0xb),
+        (V, 0xf),
+    ]
+};
 
 struct CompatibilityOptions {
     shift_ignores_vy: bool,
     no_increment: bool,
     jump_table_variant: bool,
+    fx1e_sets_vf: bool,
 }
 
 impl Default for CompatibilityOptions {
@@ -13,12 +65,34 @@ impl Default for CompatibilityOptions {
             shift_ignores_vy: false,
             no_increment: false,
             jump_table_variant: false,
+            fx1e_sets_vf: false,
         }
     }
 }
 
 type Addr = usize;
 
+#[derive(Debug, Clone, Copy)]
+enum FaultKind {
+    Fetch,
+    Read,
+    Write,
+    StackOverflow,
+    StackUnderflow,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fault {
+    addr: Addr,
+    kind: FaultKind,
+}
+
+impl std::fmt::Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?} fault at {:03X}", self.kind, self.addr)
+    }
+}
+
 struct Chip {
     opts: CompatibilityOptions,
     mem: Vec<u8>,
@@ -28,14 +102,20 @@ struct Chip {
     dt: u8,
     st: u8,
     regs: [u8; 16],
-    screen: [[bool; 64]; 32],
+    screen: Vec<Vec<bool>>,
+    hires: bool,
     halted: bool,
+    keys: [bool; 16],
+    trace: bool,
+    fault: Option<Fault>,
 }
 
 impl Chip {
     fn new(rom: Vec<u8>, opts: CompatibilityOptions) -> Self {
-        let mut mem: Vec<u8> = [0; 0x200].into();
-        mem.extend(rom);
+        let mut mem = vec![0u8; MEM_SIZE];
+        mem[FONT_ADDR..FONT_ADDR + FONT.len()].copy_from_slice(&FONT);
+        let rom_len = rom.len().min(MEM_SIZE - 0x200);
+        mem[0x200..0x200 + rom_len].copy_from_slice(&rom[..rom_len]);
         Self {
             opts,
             mem,
@@ -45,164 +125,213 @@ impl Chip {
             dt: 0,
             st: 0,
             regs: [0; 16],
-            screen: [[false; 64]; 32],
+            screen: vec![vec![false; 64]; 32],
+            hires: false,
             halted: false,
+            keys: [false; 16],
+            trace: false,
+            fault: None,
+        }
+    }
+
+    // Checked memory accessors: an out-of-range address records a `Fault` and halts the
+    // emulator instead of panicking, so a ROM that runs off the end of memory fails cleanly.
+    fn read_byte(&mut self, addr: Addr, kind: FaultKind) -> u8 {
+        match self.mem.get(addr) {
+            Some(&b) => b,
+            None => {
+                self.fault = Some(Fault { addr, kind });
+                self.halted = true;
+                0
+            }
+        }
+    }
+
+    fn write_byte(&mut self, addr: Addr, value: u8, kind: FaultKind) {
+        match self.mem.get_mut(addr) {
+            Some(slot) => *slot = value,
+            None => {
+                self.fault = Some(Fault { addr, kind });
+                self.halted = true;
+            }
+        }
+    }
+
+    fn push_stack(&mut self, addr: Addr) {
+        if self.stack.len() >= STACK_LIMIT {
+            self.fault = Some(Fault {
+                addr,
+                kind: FaultKind::StackOverflow,
+            });
+            self.halted = true;
+            return;
+        }
+        self.stack.push(addr);
+    }
+
+    fn pop_stack(&mut self) -> Addr {
+        match self.stack.pop() {
+            Some(addr) => addr,
+            None => {
+                self.fault = Some(Fault {
+                    addr: self.pc,
+                    kind: FaultKind::StackUnderflow,
+                });
+                self.halted = true;
+                0
+            }
+        }
+    }
+
+    // The Super-Chip extensions double the plane to 128x64; `hires` tracks which of the
+    // two resolutions is currently active.
+    fn screen_dims(&self) -> (usize, usize) {
+        if self.hires {
+            (128, 64)
+        } else {
+            (64, 32)
+        }
+    }
+
+    fn clear_screen(&mut self) {
+        let (w, h) = self.screen_dims();
+        self.screen = vec![vec![false; w]; h];
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        let (w, h) = self.screen_dims();
+        let mut scrolled = vec![vec![false; w]; h];
+        if n < h {
+            scrolled[n..h].clone_from_slice(&self.screen[..h - n]);
+        }
+        self.screen = scrolled;
+    }
+
+    fn scroll_right(&mut self) {
+        let (w, _) = self.screen_dims();
+        for row in self.screen.iter_mut() {
+            for x in (0..w).rev() {
+                row[x] = x >= 4 && row[x - 4];
+            }
+        }
+    }
+
+    fn scroll_left(&mut self) {
+        let (w, _) = self.screen_dims();
+        for row in self.screen.iter_mut() {
+            for x in 0..w {
+                row[x] = x + 4 < w && row[x + 4];
+            }
         }
     }
 
     fn step(&mut self) -> bool {
-        let b1 = self.mem[self.pc];
-        let b2 = self.mem[self.pc + 1];
+        let instr_addr = self.pc;
+        let b1 = self.read_byte(instr_addr, FaultKind::Fetch);
+        let b2 = self.read_byte(instr_addr + 1, FaultKind::Fetch);
+        if self.halted {
+            return false;
+        }
         let opcode: u8 = (b1 & 0xf0) >> 4;
         let x: u8 = b1 & 0x0f;
         let y: u8 = (b2 & 0xf0) >> 4;
         let n: u8 = b2 & 0x0f;
         let nn: u8 = b2;
         let nnn: usize = (((b1 & 0x0f) as u16 * 256) | (b2 as u16)) as usize;
-        if !self.halted {
-            print!(
-                "[{:03x}] {:02X}{:02X} op={:X} x={:X} y={:X} n={:X} nn={:02X} nnn={:03X} ",
-                self.pc, b1, b2, opcode, x, y, n, nn, nnn
-            )
+        if self.trace {
+            println!("{}", disassemble(&self.mem, instr_addr));
         }
         self.pc += 2;
-        let mut desc: Option<String> = None;
         let mut drew = false;
         match opcode {
             0x0 => {
-                if nnn == 0x0e0 {
-                    desc = Some("clear screen".to_owned());
-                    self.screen = [[false; 64]; 32];
-                } else if nnn == 0x0ee {
-                    desc = Some("return".to_owned());
-                    match self.stack.pop() {
-                        None => {
-                            println!("error: stack underflow (return instruction skipped")
+                if nnn & 0xff0 == 0x0c0 {
+                    self.scroll_down(n as usize);
+                } else {
+                    match nnn {
+                        0x0e0 => self.clear_screen(),
+                        0x0ee => self.pc = self.pop_stack(),
+                        0x0fb => self.scroll_right(),
+                        0x0fc => self.scroll_left(),
+                        0x0fe => {
+                            self.hires = false;
+                            self.clear_screen();
                         }
-                        Some(addr) => self.pc = addr,
+                        0x0ff => {
+                            self.hires = true;
+                            self.clear_screen();
+                        }
+                        _ => (),
                     }
                 }
             }
             0x1 => {
-                desc = Some(format!("jump to {:03X}", nnn));
                 if self.pc == nnn + 2 {
                     self.halted = true
                 }
                 self.pc = nnn;
             }
             0x2 => {
-                desc = Some(format!("call subroutine at {:03X}", nnn));
-                self.stack.push(self.pc);
+                self.push_stack(self.pc);
                 self.pc = nnn;
             }
             0x3 => {
-                desc = Some(format!("skip if register {:X} equals {:02X}", x, nn));
                 if self.regs[x as usize] == nn {
-                    self.pc += 2;
+                    self.pc += 2
                 }
             }
             0x4 => {
-                desc = Some(format!(
-                    "skip if register {:X} does not equal {:02X}",
-                    x, nn
-                ));
                 if self.regs[x as usize] != nn {
-                    self.pc += 2;
+                    self.pc += 2
                 }
             }
             0x5 => {
-                desc = Some(format!("skip if register {:X} equals register {:X}", x, y));
                 if self.regs[x as usize] == self.regs[y as usize] {
-                    self.pc += 2;
+                    self.pc += 2
                 }
             }
             0x6 => {
-                desc = Some(format!("set register {:X} to {:02X}", x, nn));
                 self.regs[x as usize] = nn;
             }
             0x7 => {
-                desc = Some(format!("increase register {:X} by {:02X}", x, nn));
                 let r = &mut self.regs[x as usize];
                 *r = r.wrapping_add(nn);
             }
             0x8 => match n {
                 0x0 => {
-                    desc = Some(format!("set register {:X} to value in register {:X}", x, y));
                     self.regs[x as usize] = self.regs[y as usize];
                 }
                 0x1 => {
-                    desc = Some(format!(
-                        "OR register {:X} with value in register {:X}",
-                        x, y
-                    ));
                     self.regs[x as usize] |= self.regs[y as usize];
                 }
                 0x2 => {
-                    desc = Some(format!(
-                        "AND register {:X} with value in register {:X}",
-                        x, y
-                    ));
                     self.regs[x as usize] &= self.regs[y as usize];
                 }
                 0x3 => {
-                    desc = Some(format!(
-                        "XOR register {:X} with value in register {:X}",
-                        x, y
-                    ));
                     self.regs[x as usize] ^= self.regs[y as usize];
                 }
                 0x4 => {
-                    desc = Some(format!(
-                        "Increase register {:X} by value in register {:X}",
-                        x, y
-                    ));
                     let result = self.regs[x as usize] as u16 + self.regs[y as usize] as u16;
                     self.regs[x as usize] = (result & 0xff) as u8;
                     self.regs[0xf] = if result > 0xff { 1 } else { 0 };
                 }
                 0x5 | 0x7 => {
                     let (m, s) = if n == 0x5 {
-                        desc = Some(format!(
-                            "Subtract register {:X} from register {:X} and store in register {:X}",
-                            y, x, x
-                        ));
                         (self.regs[x as usize], self.regs[y as usize])
                     } else {
-                        desc = Some(format!(
-                            "Subtract register {:X} from register {:X} and store in register {:X}",
-                            x, y, x
-                        ));
                         (self.regs[y as usize], self.regs[x as usize])
                     };
                     self.regs[x as usize] = m.wrapping_sub(s);
                     self.regs[0xf] = if s > m { 0 } else { 1 };
                 }
                 0x6 => {
-                    let v = if self.opts.shift_ignores_vy {
-                        desc = Some(format!("Shift register {:X} right (*)", x));
-                        self.regs[x as usize]
-                    } else {
-                        desc = Some(format!(
-                            "Shift register {:X} right and store in register {:X} (*)",
-                            y, x
-                        ));
-                        self.regs[x as usize]
-                    };
+                    let v = self.regs[x as usize];
                     let flag = v & 0x1;
                     self.regs[x as usize] = v >> 1;
                     self.regs[0xf] = flag;
                 }
                 0xe => {
-                    let v = if self.opts.shift_ignores_vy {
-                        desc = Some(format!("Shift register {:X} left (*)", x));
-                        self.regs[x as usize]
-                    } else {
-                        desc = Some(format!(
-                            "Shift register {:X} left and store in register {:X} (*)",
-                            y, x
-                        ));
-                        self.regs[x as usize]
-                    };
+                    let v = self.regs[x as usize];
                     let flag = (v & 0b10000000) >> 7;
                     self.regs[x as usize] = (v << 1) & 0xff;
                     self.regs[0xf] = flag;
@@ -210,30 +339,17 @@ impl Chip {
                 _ => (),
             },
             0x9 => {
-                desc = Some(format!(
-                    "skip if register {:X} does not equal register {:X}",
-                    x, y
-                ));
                 if self.regs[x as usize] != self.regs[y as usize] {
                     self.pc += 2;
                 }
             }
             0xA => {
-                desc = Some(format!("set index register to {:03X}", nnn));
                 self.ir = nnn;
             }
             0xB => {
                 let offs = if self.opts.jump_table_variant {
-                    desc = Some(format!(
-                        "jump by table at {:03X} using value in register {:X} (*)",
-                        nnn, x
-                    ));
                     self.regs[x as usize]
                 } else {
-                    desc = Some(format!(
-                        "jump by table at {:03X} using value in register 0 (*)",
-                        nnn
-                    ));
                     self.regs[0]
                 };
                 let dest = nnn + offs as usize;
@@ -242,42 +358,787 @@ impl Chip {
                 }
                 self.pc = dest;
             }
+            0xC => {
+                self.regs[x as usize] = rand::thread_rng().gen::<u8>() & nn;
+            }
             0xD => {
-                desc = Some(format!(
-                    "draw {} rows with X={:X}, Y={:X} ({},{})",
-                    n, x, y, self.regs[x as usize], self.regs[y as usize]
-                ));
                 drew = true;
-                let px = (self.regs[x as usize] % 64) as usize;
-                let py = (self.regs[y as usize] % 32) as usize;
+                let (w, h) = self.screen_dims();
+                let px = (self.regs[x as usize] as usize) % w;
+                let py = (self.regs[y as usize] as usize) % h;
                 self.regs[0xf] = 0;
-                for dy in 0..(n as usize) {
-                    if py + dy >= 32 {
+                let (rows, bytes_per_row) = if n == 0 && self.hires {
+                    (16, 2)
+                } else {
+                    (n as usize, 1)
+                };
+                'rows: for dy in 0..rows {
+                    if py + dy >= h {
                         break;
                     }
-                    let data = self.mem[self.ir + dy];
-                    for dx in 0..8 {
-                        if px + dx >= 64 {
-                            break;
+                    for b in 0..bytes_per_row {
+                        let data = self.read_byte(self.ir + dy * bytes_per_row + b, FaultKind::Read);
+                        if self.halted {
+                            break 'rows;
                         }
-                        let pixel = &mut self.screen[py + dy][px + dx];
-                        let draw = (data >> (7 - dx)) & 1 == 1;
-                        if *pixel && draw {
-                            self.regs[0xf] = 1;
+                        for bit in 0..8 {
+                            let dx = b * 8 + bit;
+                            if px + dx >= w {
+                                break;
+                            }
+                            let pixel = &mut self.screen[py + dy][px + dx];
+                            let draw = (data >> (7 - bit)) & 1 == 1;
+                            if *pixel && draw {
+                                self.regs[0xf] = 1;
+                            }
+                            *pixel = *pixel ^ draw;
                         }
-                        *pixel = *pixel ^ draw;
                     }
                 }
             }
+            0xE => match nn {
+                0x9e => {
+                    if self.keys[(self.regs[x as usize] & 0xf) as usize] {
+                        self.pc += 2;
+                    }
+                }
+                0xa1 => {
+                    if !self.keys[(self.regs[x as usize] & 0xf) as usize] {
+                        self.pc += 2;
+                    }
+                }
+                _ => (),
+            },
+            0xF => match nn {
+                0x0a => match (0..16).find(|&k| self.keys[k]) {
+                    Some(k) => self.regs[x as usize] = k as u8,
+                    None => self.pc -= 2,
+                },
+                0x07 => {
+                    self.regs[x as usize] = self.dt;
+                }
+                0x15 => {
+                    self.dt = self.regs[x as usize];
+                }
+                0x18 => {
+                    self.st = self.regs[x as usize];
+                }
+                0x1e => {
+                    let result = self.ir + self.regs[x as usize] as usize;
+                    if self.opts.fx1e_sets_vf {
+                        self.regs[0xf] = if result > 0x0fff { 1 } else { 0 };
+                    }
+                    self.ir = result;
+                }
+                0x29 => {
+                    self.ir = FONT_ADDR + (self.regs[x as usize] as usize & 0xf) * 5;
+                }
+                0x33 => {
+                    let v = self.regs[x as usize];
+                    self.write_byte(self.ir, v / 100, FaultKind::Write);
+                    self.write_byte(self.ir + 1, (v / 10) % 10, FaultKind::Write);
+                    self.write_byte(self.ir + 2, v % 10, FaultKind::Write);
+                }
+                0x55 => {
+                    for i in 0..=(x as usize) {
+                        self.write_byte(self.ir + i, self.regs[i], FaultKind::Write);
+                        if self.halted {
+                            break;
+                        }
+                    }
+                    if !self.opts.no_increment {
+                        self.ir += x as usize + 1;
+                    }
+                }
+                0x65 => {
+                    for i in 0..=(x as usize) {
+                        self.regs[i] = self.read_byte(self.ir + i, FaultKind::Read);
+                        if self.halted {
+                            break;
+                        }
+                    }
+                    if !self.opts.no_increment {
+                        self.ir += x as usize + 1;
+                    }
+                }
+                _ => (),
+            },
             _ => (),
         }
-        if !self.halted {
-            match desc {
-                None => println!("unknown opcode"),
-                Some(d) => println!("{}", d),
+        drew
+    }
+
+    fn tick_timers(&mut self) {
+        if self.dt > 0 {
+            self.dt -= 1;
+        }
+        if self.st > 0 {
+            self.st -= 1;
+        }
+    }
+
+    fn dt(&self) -> u8 {
+        self.dt
+    }
+
+    fn st(&self) -> u8 {
+        self.st
+    }
+
+    fn save_snapshot(&self, path: &str) -> std::io::Result<()> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.push(SNAPSHOT_VERSION);
+        buf.extend_from_slice(&(self.mem.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.mem);
+        buf.extend_from_slice(&(self.pc as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.ir as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.stack.len() as u32).to_le_bytes());
+        for addr in &self.stack {
+            buf.extend_from_slice(&(*addr as u32).to_le_bytes());
+        }
+        buf.push(self.dt);
+        buf.push(self.st);
+        buf.extend_from_slice(&self.regs);
+        buf.push(self.hires as u8);
+        for row in &self.screen {
+            for &pixel in row {
+                buf.push(pixel as u8);
             }
         }
-        drew
+        buf.push(self.halted as u8);
+        buf.push(self.opts.shift_ignores_vy as u8);
+        buf.push(self.opts.no_increment as u8);
+        buf.push(self.opts.jump_table_variant as u8);
+        buf.push(self.opts.fx1e_sets_vf as u8);
+        std::fs::write(path, buf)
+    }
+
+    fn load_snapshot(&mut self, path: &str) -> Result<(), SnapshotError> {
+        let buf = std::fs::read(path)?;
+        let mut r = SnapshotReader::new(&buf);
+        if r.read_bytes(4)? != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = r.read_u8()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let mem_len = r.read_u32()? as usize;
+        if mem_len != self.mem.len() {
+            return Err(SnapshotError::MemSizeMismatch {
+                expected: self.mem.len(),
+                found: mem_len,
+            });
+        }
+        let mem = r.read_bytes(mem_len)?.to_vec();
+        let pc = r.read_u32()? as usize;
+        let ir = r.read_u32()? as usize;
+        let stack_len = r.read_u32()? as usize;
+        let mut stack = Vec::with_capacity(stack_len);
+        for _ in 0..stack_len {
+            stack.push(r.read_u32()? as usize);
+        }
+        let dt = r.read_u8()?;
+        let st = r.read_u8()?;
+        let mut regs = [0u8; 16];
+        regs.copy_from_slice(r.read_bytes(16)?);
+        let hires = r.read_u8()? != 0;
+        let (screen_w, screen_h) = if hires { (128, 64) } else { (64, 32) };
+        let mut screen = vec![vec![false; screen_w]; screen_h];
+        for row in screen.iter_mut() {
+            for pixel in row.iter_mut() {
+                *pixel = r.read_u8()? != 0;
+            }
+        }
+        let halted = r.read_u8()? != 0;
+        let opts = CompatibilityOptions {
+            shift_ignores_vy: r.read_u8()? != 0,
+            no_increment: r.read_u8()? != 0,
+            jump_table_variant: r.read_u8()? != 0,
+            fx1e_sets_vf: r.read_u8()? != 0,
+        };
+
+        self.mem = mem;
+        self.pc = pc;
+        self.ir = ir;
+        self.stack = stack;
+        self.dt = dt;
+        self.st = st;
+        self.regs = regs;
+        self.hires = hires;
+        self.screen = screen;
+        self.halted = halted;
+        self.opts = opts;
+        Ok(())
+    }
+}
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"CH8S";
+const SNAPSHOT_VERSION: u8 = 2;
+const SNAPSHOT_PATH: &str = "snapshot.ch8s";
+
+// A minimal cursor over a snapshot buffer, returning `Truncated` instead of panicking
+// whenever a mismatched or cut-off file runs out of bytes to read.
+struct SnapshotReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        let end = self.pos + n;
+        if end > self.data.len() {
+            return Err(SnapshotError::Truncated);
+        }
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SnapshotError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[derive(Debug)]
+enum SnapshotError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    MemSizeMismatch { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "i/o error: {}", e),
+            SnapshotError::BadMagic => write!(f, "not a chipr snapshot file"),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {}", v),
+            SnapshotError::Truncated => write!(f, "snapshot file is truncated"),
+            SnapshotError::MemSizeMismatch { expected, found } => write!(
+                f,
+                "snapshot memory size {} does not match expected {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+// A minimal square wave, fixed at a low amplitude so it doesn't clip, for the FX18 sound timer beep.
+struct SquareWave {
+    freq: f32,
+    sample_rate: u32,
+    phase: f32,
+}
+
+impl SquareWave {
+    fn new(freq: f32, sample_rate: u32) -> Self {
+        Self {
+            freq,
+            sample_rate,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Iterator for SquareWave {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.phase = (self.phase + self.freq / self.sample_rate as f32) % 1.0;
+        Some(if self.phase < 0.5 { 0.2 } else { -0.2 })
+    }
+}
+
+impl rodio::Source for SquareWave {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+// A stepping debugger: a REPL over stdin that the event loop drops into whenever the emulator
+// is paused or hits a breakpoint.
+struct Debugger {
+    breakpoints: std::collections::HashSet<Addr>,
+    last_command: String,
+}
+
+impl Debugger {
+    fn new() -> Self {
+        Self {
+            breakpoints: std::collections::HashSet::new(),
+            last_command: String::new(),
+        }
+    }
+
+    // Blocks reading commands from stdin until the user issues `continue` (or EOF).
+    fn repl(&mut self, chip: &mut Chip) {
+        self.print_regs(chip);
+        loop {
+            use std::io::Write;
+            print!("(chipr) ");
+            std::io::stdout().flush().ok();
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim();
+            let command = if line.is_empty() {
+                self.last_command.clone()
+            } else {
+                line.to_owned()
+            };
+            self.last_command = command.clone();
+            let mut parts = command.split_whitespace();
+            match parts.next() {
+                Some("step") => {
+                    let n: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+                    for _ in 0..n {
+                        chip.step();
+                    }
+                    self.print_regs(chip);
+                }
+                Some("continue") => return,
+                Some("break") => match parts.next().and_then(parse_addr) {
+                    Some(addr) => {
+                        self.breakpoints.insert(addr);
+                    }
+                    None => println!("usage: break <addr>"),
+                },
+                Some("delete") => match parts.next().and_then(parse_addr) {
+                    Some(addr) => {
+                        self.breakpoints.remove(&addr);
+                    }
+                    None => println!("usage: delete <addr>"),
+                },
+                Some("regs") => self.print_regs(chip),
+                Some("mem") => {
+                    let addr = parts.next().and_then(parse_addr);
+                    let len = parts.next().and_then(|s| s.parse::<usize>().ok());
+                    match (addr, len) {
+                        (Some(addr), Some(len)) => self.print_mem(chip, addr, len),
+                        _ => println!("usage: mem <addr> <len>"),
+                    }
+                }
+                Some("trace") => match parts.next() {
+                    Some("on") => chip.trace = true,
+                    Some("off") => chip.trace = false,
+                    _ => println!("usage: trace on|off"),
+                },
+                Some("fault") => match chip.fault {
+                    Some(fault) => println!("{}", fault),
+                    None => println!("no fault"),
+                },
+                Some(other) => println!("unknown command: {}", other),
+                None => (),
+            }
+        }
+    }
+
+    fn print_regs(&self, chip: &Chip) {
+        for i in 0..16 {
+            print!("V{:X}={:02X} ", i, chip.regs[i]);
+            if i % 4 == 3 {
+                println!();
+            }
+        }
+        println!(
+            "I={:03X} PC={:03X} SP={} DT={:02X} ST={:02X}",
+            chip.ir,
+            chip.pc,
+            chip.stack.len(),
+            chip.dt(),
+            chip.st()
+        );
+    }
+
+    fn print_mem(&self, chip: &Chip, addr: Addr, len: usize) {
+        let end = (addr + len).min(chip.mem.len());
+        if addr >= end {
+            return;
+        }
+        for (i, chunk) in chip.mem[addr..end].chunks(16).enumerate() {
+            print!("{:03X}: ", addr + i * 16);
+            for b in chunk {
+                print!("{:02X} ", b);
+            }
+            println!();
+        }
+    }
+}
+
+fn parse_addr(s: &str) -> Option<Addr> {
+    Addr::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+// Decodes the opcode at `addr` into a human-readable line, without touching any machine state.
+// This is the same decoding `step` uses to build its trace output, but it only describes the
+// instruction in the abstract (symbolic register names, no compatibility-quirk branching) since
+// it has no `Chip` to read concrete values or options from.
+fn disassemble(bytes: &[u8], addr: Addr) -> String {
+    if addr + 1 >= bytes.len() {
+        return format!("[{:03x}] <out of range>", addr);
+    }
+    let b1 = bytes[addr];
+    let b2 = bytes[addr + 1];
+    let opcode: u8 = (b1 & 0xf0) >> 4;
+    let x: u8 = b1 & 0x0f;
+    let y: u8 = (b2 & 0xf0) >> 4;
+    let n: u8 = b2 & 0x0f;
+    let nn: u8 = b2;
+    let nnn: usize = (((b1 & 0x0f) as u16 * 256) | (b2 as u16)) as usize;
+    let desc = match opcode {
+        0x0 if nnn & 0xff0 == 0x0c0 => format!("scroll down {} pixels", n),
+        0x0 if nnn == 0x0e0 => "clear screen".to_owned(),
+        0x0 if nnn == 0x0ee => "return".to_owned(),
+        0x0 if nnn == 0x0fb => "scroll right 4 pixels".to_owned(),
+        0x0 if nnn == 0x0fc => "scroll left 4 pixels".to_owned(),
+        0x0 if nnn == 0x0fe => "switch to low-resolution mode".to_owned(),
+        0x0 if nnn == 0x0ff => "switch to high-resolution mode".to_owned(),
+        0x1 => format!("jump to {:03X}", nnn),
+        0x2 => format!("call subroutine at {:03X}", nnn),
+        0x3 => format!("skip if register {:X} equals {:02X}", x, nn),
+        0x4 => format!("skip if register {:X} does not equal {:02X}", x, nn),
+        0x5 => format!("skip if register {:X} equals register {:X}", x, y),
+        0x6 => format!("set register {:X} to {:02X}", x, nn),
+        0x7 => format!("increase register {:X} by {:02X}", x, nn),
+        0x8 => match n {
+            0x0 => format!("set register {:X} to value in register {:X}", x, y),
+            0x1 => format!("OR register {:X} with value in register {:X}", x, y),
+            0x2 => format!("AND register {:X} with value in register {:X}", x, y),
+            0x3 => format!("XOR register {:X} with value in register {:X}", x, y),
+            0x4 => format!("Increase register {:X} by value in register {:X}", x, y),
+            0x5 => format!(
+                "Subtract register {:X} from register {:X} and store in register {:X}",
+                y, x, x
+            ),
+            0x7 => format!(
+                "Subtract register {:X} from register {:X} and store in register {:X}",
+                x, y, x
+            ),
+            0x6 => format!(
+                "Shift register {:X} right and store in register {:X} (*)",
+                y, x
+            ),
+            0xe => format!(
+                "Shift register {:X} left and store in register {:X} (*)",
+                y, x
+            ),
+            _ => "unknown opcode".to_owned(),
+        },
+        0x9 => format!(
+            "skip if register {:X} does not equal register {:X}",
+            x, y
+        ),
+        0xA => format!("set index register to {:03X}", nnn),
+        0xB => format!(
+            "jump by table at {:03X} using value in register {:X} or 0 (*)",
+            nnn, x
+        ),
+        0xC => format!(
+            "set register {:X} to random value masked with {:02X}",
+            x, nn
+        ),
+        0xD if n == 0 => format!("draw 16x16 sprite with X={:X}, Y={:X}", x, y),
+        0xD => format!("draw {} rows with X={:X}, Y={:X}", n, x, y),
+        0xE => match nn {
+            0x9e => format!("skip if key in register {:X} is pressed", x),
+            0xa1 => format!("skip if key in register {:X} is not pressed", x),
+            _ => "unknown opcode".to_owned(),
+        },
+        0xF => match nn {
+            0x07 => format!("set register {:X} to value of delay timer", x),
+            0x0a => format!("wait for key press and store in register {:X}", x),
+            0x15 => format!("set delay timer to value of register {:X}", x),
+            0x18 => format!("set sound timer to value of register {:X}", x),
+            0x1e => format!("increase index register by value in register {:X}", x),
+            0x29 => format!(
+                "set index register to font sprite for digit in register {:X}",
+                x
+            ),
+            0x33 => format!(
+                "store binary-coded decimal of register {:X} at index register",
+                x
+            ),
+            0x55 => format!(
+                "store registers 0 through {:X} to memory at index register",
+                x
+            ),
+            0x65 => format!(
+                "load registers 0 through {:X} from memory at index register",
+                x
+            ),
+            _ => "unknown opcode".to_owned(),
+        },
+        _ => "unknown opcode".to_owned(),
+    };
+    format!(
+        "[{:03x}] {:02X}{:02X} op={:X} x={:X} y={:X} n={:X} nn={:02X} nnn={:03X} {}",
+        addr, b1, b2, opcode, x, y, n, nn, nnn, desc
+    )
+}
+
+fn run_disassembler(rom: &[u8]) {
+    let mut mem: Vec<u8> = [0; 0x200].into();
+    mem.extend_from_slice(rom);
+    let mut addr = 0x200;
+    while addr + 1 < mem.len() {
+        println!("{}", disassemble(&mem, addr));
+        addr += 2;
+    }
+}
+
+#[derive(Debug)]
+enum AssembleError {
+    UnknownMnemonic(usize, String),
+    UnknownOperand(usize, String),
+    WrongOperandCount(usize, String),
+}
+
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AssembleError::UnknownMnemonic(line, m) => {
+                write!(f, "line {}: unknown mnemonic '{}'", line, m)
+            }
+            AssembleError::UnknownOperand(line, o) => {
+                write!(f, "line {}: unknown operand '{}'", line, o)
+            }
+            AssembleError::WrongOperandCount(line, instr) => {
+                write!(f, "line {}: wrong number of operands for '{}'", line, instr)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+fn parse_register(s: &str) -> Option<u8> {
+    let s = s.trim_end_matches(',');
+    if s.len() != 2 || !s.starts_with(['V', 'v']) {
+        return None;
+    }
+    u8::from_str_radix(&s[1..], 16).ok().filter(|&v| v < 16)
+}
+
+fn parse_immediate(s: &str) -> Option<u32> {
+    let s = s.trim_end_matches(',');
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn resolve_addr(
+    operand: &str,
+    symbols: &std::collections::HashMap<String, Addr>,
+    line: usize,
+) -> Result<Addr, AssembleError> {
+    let operand = operand.trim_end_matches(',');
+    if let Some(&addr) = symbols.get(operand) {
+        return Ok(addr);
+    }
+    parse_immediate(operand)
+        .map(|v| v as Addr)
+        .ok_or_else(|| AssembleError::UnknownOperand(line, operand.to_owned()))
+}
+
+// Assembles the line-oriented mnemonic syntax (`SET V0, 0x12`, `JMP 0x200`, `DRAW V0, V1, 5`,
+// with `label:` lines resolved in a second pass) into a flat ROM image starting at 0x200.
+fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    let mut symbols: std::collections::HashMap<String, Addr> = std::collections::HashMap::new();
+    let mut instructions: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut addr = 0x200;
+    for (i, raw) in source.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(label) = line.strip_suffix(':') {
+            symbols.insert(label.trim().to_owned(), addr);
+            continue;
+        }
+        let tokens: Vec<String> = line.split_whitespace().map(|s| s.to_owned()).collect();
+        instructions.push((i + 1, tokens));
+        addr += 2;
+    }
+
+    let mut out = Vec::with_capacity(instructions.len() * 2);
+    for (line, tokens) in &instructions {
+        let word = encode_instruction(tokens, &symbols, *line)?;
+        out.extend_from_slice(&word.to_be_bytes());
+    }
+    Ok(out)
+}
+
+fn encode_instruction(
+    tokens: &[String],
+    symbols: &std::collections::HashMap<String, Addr>,
+    line: usize,
+) -> Result<u16, AssembleError> {
+    let mnemonic = tokens.first().map(|s| s.to_uppercase()).unwrap_or_default();
+    let ops = &tokens[1..];
+    let bad_count = || AssembleError::WrongOperandCount(line, mnemonic.clone());
+    let bad_operand = |o: &str| AssembleError::UnknownOperand(line, o.to_owned());
+
+    let one_reg = || -> Result<u8, AssembleError> {
+        let r = ops.first().ok_or_else(bad_count)?;
+        parse_register(r).ok_or_else(|| bad_operand(r))
+    };
+    let two_regs = || -> Result<(u8, u8), AssembleError> {
+        let (rx, ry) = (ops.first(), ops.get(1));
+        match (rx, ry) {
+            (Some(rx), Some(ry)) => Ok((
+                parse_register(rx).ok_or_else(|| bad_operand(rx))?,
+                parse_register(ry).ok_or_else(|| bad_operand(ry))?,
+            )),
+            _ => Err(bad_count()),
+        }
+    };
+    let reg_imm = || -> Result<(u8, u8), AssembleError> {
+        let (r, v) = (ops.first(), ops.get(1));
+        match (r, v) {
+            (Some(r), Some(v)) => Ok((
+                parse_register(r).ok_or_else(|| bad_operand(r))?,
+                parse_immediate(v).ok_or_else(|| bad_operand(v))? as u8,
+            )),
+            _ => Err(bad_count()),
+        }
+    };
+    let addr_operand = || -> Result<Addr, AssembleError> {
+        let a = ops.first().ok_or_else(bad_count)?;
+        resolve_addr(a, symbols, line)
+    };
+
+    match mnemonic.as_str() {
+        "CLS" => Ok(0x00e0),
+        "RET" => Ok(0x00ee),
+        "JMP" => Ok(0x1000 | addr_operand()? as u16),
+        "CALL" => Ok(0x2000 | addr_operand()? as u16),
+        "SKEQ" => {
+            let (x, nn) = reg_imm()?;
+            Ok(0x3000 | (x as u16) << 8 | nn as u16)
+        }
+        "SKNE" => {
+            let (x, nn) = reg_imm()?;
+            Ok(0x4000 | (x as u16) << 8 | nn as u16)
+        }
+        "SKEQR" => {
+            let (x, y) = two_regs()?;
+            Ok(0x5000 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SET" => {
+            let (x, nn) = reg_imm()?;
+            Ok(0x6000 | (x as u16) << 8 | nn as u16)
+        }
+        "ADD" => {
+            let (x, nn) = reg_imm()?;
+            Ok(0x7000 | (x as u16) << 8 | nn as u16)
+        }
+        "SETR" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8000 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "OR" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8001 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "AND" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8002 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "XOR" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8003 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "ADDR" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8004 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SUB" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8005 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SHR" => {
+            let x = one_reg()?;
+            let y = if ops.len() > 1 { two_regs()?.1 } else { x };
+            Ok(0x8006 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SUBN" => {
+            let (x, y) = two_regs()?;
+            Ok(0x8007 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SHL" => {
+            let x = one_reg()?;
+            let y = if ops.len() > 1 { two_regs()?.1 } else { x };
+            Ok(0x800e | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SKNER" => {
+            let (x, y) = two_regs()?;
+            Ok(0x9000 | (x as u16) << 8 | (y as u16) << 4)
+        }
+        "SETI" => Ok(0xa000 | addr_operand()? as u16),
+        "JMPV" => Ok(0xb000 | addr_operand()? as u16),
+        "RND" => {
+            let (x, nn) = reg_imm()?;
+            Ok(0xc000 | (x as u16) << 8 | nn as u16)
+        }
+        "DRAW" => {
+            let (x, y) = (ops.first(), ops.get(1));
+            let n = ops.get(2);
+            match (x, y, n) {
+                (Some(x), Some(y), Some(n)) => {
+                    let x = parse_register(x).ok_or_else(|| bad_operand(x))?;
+                    let y = parse_register(y).ok_or_else(|| bad_operand(y))?;
+                    let n = parse_immediate(n).ok_or_else(|| bad_operand(n))? as u16;
+                    Ok(0xd000 | (x as u16) << 8 | (y as u16) << 4 | (n & 0xf))
+                }
+                _ => Err(bad_count()),
+            }
+        }
+        "SKPR" => Ok(0xe09e | (one_reg()? as u16) << 8),
+        "SKUP" => Ok(0xe0a1 | (one_reg()? as u16) << 8),
+        "GDELAY" => Ok(0xf007 | (one_reg()? as u16) << 8),
+        "KEY" => Ok(0xf00a | (one_reg()? as u16) << 8),
+        "SDELAY" => Ok(0xf015 | (one_reg()? as u16) << 8),
+        "SSOUND" => Ok(0xf018 | (one_reg()? as u16) << 8),
+        "ADDI" => Ok(0xf01e | (one_reg()? as u16) << 8),
+        "FONT" => Ok(0xf029 | (one_reg()? as u16) << 8),
+        "BCD" => Ok(0xf033 | (one_reg()? as u16) << 8),
+        "SAVE" => Ok(0xf055 | (one_reg()? as u16) << 8),
+        "LOAD" => Ok(0xf065 | (one_reg()? as u16) << 8),
+        _ => Err(AssembleError::UnknownMnemonic(line, mnemonic)),
     }
 }
 
@@ -288,6 +1149,8 @@ fn main() {
         None => panic!("expected at least one argument"),
         Some(arg) => arg,
     };
+    let mut disasm_mode = false;
+    let mut asm_mode = false;
     for arg in a {
         if arg == "--shift" {
             println!("Super-Chip compatibility: 8XY6 and 8XYE ignore their second operand");
@@ -297,11 +1160,35 @@ fn main() {
                 "Super-Chip compatibility: BNNN uses VX rather than V0 for the jump table index"
             );
             opts.jump_table_variant = true;
+        } else if arg == "--fx1e" {
+            println!("Amiga compatibility: FX1E sets VF when IR overflows past 0x0FFF");
+            opts.fx1e_sets_vf = true;
+        } else if arg == "--disasm" {
+            disasm_mode = true;
+        } else if arg == "--asm" {
+            asm_mode = true;
         } else {
             panic!("unknown argument")
         }
     }
-    let bytes = std::fs::read(filename).expect("could not read ROM file");
+    if asm_mode {
+        let source = std::fs::read_to_string(&filename).expect("could not read source file");
+        match assemble(&source) {
+            Ok(rom) => {
+                use std::io::Write;
+                std::io::stdout()
+                    .write_all(&rom)
+                    .expect("failed to write assembled ROM to stdout");
+            }
+            Err(e) => panic!("assembly failed: {}", e),
+        }
+        return;
+    }
+    let bytes = std::fs::read(&filename).expect("could not read ROM file");
+    if disasm_mode {
+        run_disassembler(&bytes);
+        return;
+    }
     let mut chip = Chip::new(bytes, opts);
     println!("{} bytes in memory", chip.mem.len());
 
@@ -322,12 +1209,27 @@ fn main() {
             pixels::SurfaceTexture::new(window_size.width, window_size.height, &window);
         pixels::Pixels::new(64, 32, surface_texture).unwrap()
     };
+    let (_audio_stream, audio_handle) = rodio::OutputStream::try_default().unwrap();
+    let sink = rodio::Sink::try_new(&audio_handle).unwrap();
+    sink.append(SquareWave::new(440.0, 44100));
+    sink.pause();
+
     let start = std::time::Instant::now();
     let mut spent = std::time::Duration::from_secs(0);
+    let timer_start = std::time::Instant::now();
+    let mut timer_spent = std::time::Duration::from_secs(0);
     let mut halt_detected = false;
+    let mut debugger = Debugger::new();
+    let mut hires = chip.hires;
     event_loop.run(move |event, _, control_flow| {
+        if chip.hires != hires {
+            hires = chip.hires;
+            let (w, h) = if hires { (128, 64) } else { (64, 32) };
+            pixels.resize_buffer(w, h);
+        }
         if let winit::event::Event::RedrawRequested(_) = event {
-            for (y, row) in pixels.get_frame().chunks_exact_mut(64 * 4).enumerate() {
+            let width = if hires { 128 } else { 64 };
+            for (y, row) in pixels.get_frame().chunks_exact_mut(width * 4).enumerate() {
                 for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
                     let c = if chip.screen[y][x] { 0xff } else { 0x11 };
                     pixel.copy_from_slice(&[0, c, 0, 0]);
@@ -347,8 +1249,40 @@ fn main() {
         if let Some(size) = input.window_resized() {
             pixels.resize_surface(size.width, size.height);
         }
+        if input.key_pressed(winit::event::VirtualKeyCode::F5) {
+            match chip.save_snapshot(SNAPSHOT_PATH) {
+                Ok(()) => println!("saved snapshot to {}", SNAPSHOT_PATH),
+                Err(e) => println!("failed to save snapshot: {}", e),
+            }
+        }
+        if input.key_pressed(winit::event::VirtualKeyCode::F9) {
+            match chip.load_snapshot(SNAPSHOT_PATH) {
+                Ok(()) => println!("restored snapshot from {}", SNAPSHOT_PATH),
+                Err(e) => println!("failed to restore snapshot: {}", e),
+            }
+        }
+        if input.key_pressed(winit::event::VirtualKeyCode::Space) {
+            println!("paused, entering debugger (type 'continue' to resume)");
+            debugger.repl(&mut chip);
+        }
+        for (key, index) in KEYMAP {
+            chip.keys[index as usize] = input.key_held(key);
+        }
+        while std::time::Instant::now().duration_since(timer_start) > timer_spent {
+            chip.tick_timers();
+            timer_spent += std::time::Duration::from_secs(1) / DHZ;
+        }
+        if chip.st() > 0 {
+            sink.play();
+        } else {
+            sink.pause();
+        }
         let mut redraw = false;
         while std::time::Instant::now().duration_since(start) > spent {
+            if debugger.breakpoints.contains(&chip.pc) {
+                println!("breakpoint hit at {:03X}", chip.pc);
+                debugger.repl(&mut chip);
+            }
             if chip.step() {
                 spent += std::time::Duration::from_secs(1) / HZ;
                 redraw = true;
@@ -361,7 +1295,184 @@ fn main() {
         }
         if chip.halted && !halt_detected {
             halt_detected = true;
-            println!("(program entered an infinite loop)");
+            match chip.fault {
+                Some(fault) => println!("(halted: {})", fault),
+                None => println!("(program entered an infinite loop)"),
+            }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_path(name: &str) -> String {
+        format!("{}/chipr-test-{}-{}.ch8s", std::env::temp_dir().display(), name, std::process::id())
+    }
+
+    #[test]
+    fn snapshot_round_trips_full_state() {
+        let mut chip = Chip::new(vec![0x00, 0xe0], CompatibilityOptions::default());
+        chip.pc = 0x234;
+        chip.ir = 0x456;
+        chip.regs[3] = 0x42;
+        chip.dt = 7;
+        chip.st = 9;
+        chip.push_stack(0x300);
+        chip.hires = true;
+        chip.clear_screen();
+        chip.screen[0][0] = true;
+
+        let path = snapshot_path("roundtrip");
+        chip.save_snapshot(&path).unwrap();
+
+        let mut loaded = Chip::new(Vec::new(), CompatibilityOptions::default());
+        loaded.load_snapshot(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.pc, chip.pc);
+        assert_eq!(loaded.ir, chip.ir);
+        assert_eq!(loaded.regs, chip.regs);
+        assert_eq!(loaded.dt, chip.dt);
+        assert_eq!(loaded.st, chip.st);
+        assert_eq!(loaded.stack, chip.stack);
+        assert_eq!(loaded.hires, chip.hires);
+        assert_eq!(loaded.screen, chip.screen);
+    }
+
+    #[test]
+    fn load_snapshot_rejects_bad_magic() {
+        let path = snapshot_path("bad-magic");
+        std::fs::write(&path, b"NOPE!garbage").unwrap();
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        let err = chip.load_snapshot(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, SnapshotError::BadMagic));
+    }
+
+    #[test]
+    fn load_snapshot_rejects_truncated_file() {
+        let mut chip = Chip::new(vec![0x12, 0x34], CompatibilityOptions::default());
+        let path = snapshot_path("truncated");
+        chip.save_snapshot(&path).unwrap();
+        let mut buf = std::fs::read(&path).unwrap();
+        buf.truncate(buf.len() / 2);
+        std::fs::write(&path, &buf).unwrap();
+
+        let mut loaded = Chip::new(Vec::new(), CompatibilityOptions::default());
+        let err = loaded.load_snapshot(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, SnapshotError::Truncated));
+    }
+
+    #[test]
+    fn assemble_encodes_known_mnemonics() {
+        let rom = assemble("SET V0, 0x12\nSETI 0x300\nDRAW V0, V1, 5\n").unwrap();
+        assert_eq!(rom, vec![0x60, 0x12, 0xa3, 0x00, 0xd0, 0x15]);
+    }
+
+    #[test]
+    fn assemble_resolves_labels_in_second_pass() {
+        let rom = assemble("loop:\nJMP loop\n").unwrap();
+        assert_eq!(rom, vec![0x12, 0x00]);
+    }
+
+    #[test]
+    fn assemble_rejects_unknown_mnemonic() {
+        let err = assemble("FROB V0, V1\n").unwrap_err();
+        assert!(matches!(err, AssembleError::UnknownMnemonic(1, m) if m == "FROB"));
+    }
+
+    #[test]
+    fn disassemble_round_trips_assembled_opcodes() {
+        let rom = assemble("SET V3, 0x42\nSETI 0x300\n").unwrap();
+        let mut mem: Vec<u8> = vec![0; 0x200];
+        mem.extend_from_slice(&rom);
+        assert!(disassemble(&mem, 0x200).contains("set register 3 to 42"));
+        assert!(disassemble(&mem, 0x202).contains("set index register to 300"));
+    }
+
+    #[test]
+    fn disassemble_reports_out_of_range_address() {
+        let mem = vec![0u8; 4];
+        assert!(disassemble(&mem, 10).contains("out of range"));
+    }
+
+    #[test]
+    fn fetch_past_end_of_memory_faults_instead_of_panicking() {
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        chip.pc = MEM_SIZE - 1;
+        assert!(!chip.step());
+        assert!(chip.halted);
+        assert!(matches!(
+            chip.fault,
+            Some(Fault {
+                addr: MEM_SIZE,
+                kind: FaultKind::Fetch,
+            })
+        ));
+    }
+
+    #[test]
+    fn read_byte_past_end_of_memory_faults() {
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        chip.read_byte(MEM_SIZE, FaultKind::Read);
+        assert!(chip.halted);
+        assert!(matches!(
+            chip.fault,
+            Some(Fault {
+                addr: MEM_SIZE,
+                kind: FaultKind::Read,
+            })
+        ));
+    }
+
+    #[test]
+    fn write_byte_past_end_of_memory_faults() {
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        chip.write_byte(MEM_SIZE, 0, FaultKind::Write);
+        assert!(chip.halted);
+        assert!(matches!(
+            chip.fault,
+            Some(Fault {
+                addr: MEM_SIZE,
+                kind: FaultKind::Write,
+            })
+        ));
+    }
+
+    #[test]
+    fn stack_overflow_faults_instead_of_panicking() {
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        for i in 0..STACK_LIMIT {
+            chip.push_stack(i);
+        }
+        assert!(!chip.halted);
+        chip.push_stack(0x999);
+        assert!(chip.halted);
+        assert!(matches!(
+            chip.fault,
+            Some(Fault {
+                addr: 0x999,
+                kind: FaultKind::StackOverflow,
+            })
+        ));
+    }
+
+    #[test]
+    fn stack_underflow_faults_instead_of_panicking() {
+        let mut chip = Chip::new(Vec::new(), CompatibilityOptions::default());
+        chip.pc = 0x321;
+        let addr = chip.pop_stack();
+        assert!(chip.halted);
+        assert_eq!(addr, 0);
+        assert!(matches!(
+            chip.fault,
+            Some(Fault {
+                addr: 0x321,
+                kind: FaultKind::StackUnderflow,
+            })
+        ));
+    }
+}